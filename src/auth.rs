@@ -0,0 +1,180 @@
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+use warp::Filter;
+
+#[derive(Clone)]
+pub struct UserStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+const CREATE_USERS_TABLE: &str =
+    "CREATE TABLE IF NOT EXISTS users (id TEXT PRIMARY KEY, pubkey TEXT UNIQUE)";
+const INSERT_USER: &str = "INSERT INTO users (id, pubkey) VALUES (:id, :pubkey)";
+const SELECT_PUBKEY_BY_ID: &str = "SELECT pubkey FROM users WHERE id=:id";
+
+pub fn new_user_store() -> Result<UserStore, rusqlite::Error> {
+    match Connection::open("data.db") {
+        Ok(conn) => match conn.execute(CREATE_USERS_TABLE, ()) {
+            Ok(_) => Ok(UserStore {
+                conn: Arc::new(Mutex::new(conn)),
+            }),
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+impl UserStore {
+    pub fn register(&self, pubkey_hex: String) -> Option<String> {
+        let id = Uuid::new_v4().to_string();
+        let conn = self.conn.lock().unwrap();
+        match conn.execute(
+            INSERT_USER,
+            &[(":id", id.as_str()), (":pubkey", pubkey_hex.as_str())],
+        ) {
+            Ok(_) => Some(id),
+            Err(e) => {
+                println!("register error, err={:?}", e);
+                None
+            }
+        }
+    }
+
+    pub fn get_pubkey(&self, id: &str) -> Option<String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(SELECT_PUBKEY_BY_ID, &[(":id", id)], |row| row.get(0))
+            .ok()
+    }
+}
+
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    if !s.is_ascii() || s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+pub(crate) fn verify_signature(pubkey_hex: &str, signature_hex: &str, body: &[u8]) -> bool {
+    let pubkey_bytes = match hex_decode(pubkey_hex).and_then(|b| <[u8; 32]>::try_from(b).ok()) {
+        Some(b) => b,
+        None => return false,
+    };
+    let signature_bytes =
+        match hex_decode(signature_hex).and_then(|b| <[u8; 64]>::try_from(b).ok()) {
+            Some(b) => b,
+            None => return false,
+        };
+    let verifying_key = match VerifyingKey::from_bytes(&pubkey_bytes) {
+        Ok(k) => k,
+        Err(_) => return false,
+    };
+    let signature = Signature::from_bytes(&signature_bytes);
+    verifying_key.verify(body, &signature).is_ok()
+}
+
+#[derive(Debug)]
+pub enum AuthError {
+    UnknownUser,
+    InvalidSignature,
+}
+
+impl warp::reject::Reject for AuthError {}
+
+fn with_user_store(
+    user_store: UserStore,
+) -> impl Filter<Extract = (UserStore,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || user_store.clone())
+}
+
+pub fn with_auth(
+    user_store: UserStore,
+) -> impl Filter<Extract = (String, bytes::Bytes), Error = warp::Rejection> + Clone {
+    warp::header::<String>("x-user-id")
+        .and(warp::header::<String>("x-signature"))
+        .and(warp::body::bytes())
+        .and(with_user_store(user_store))
+        .and_then(
+            |user_id: String, signature: String, body: bytes::Bytes, user_store: UserStore| async move {
+                match user_store.get_pubkey(&user_id) {
+                    Some(pubkey) => {
+                        if verify_signature(&pubkey, &signature, &body) {
+                            Ok((user_id, body))
+                        } else {
+                            Err(warp::reject::custom(AuthError::InvalidSignature))
+                        }
+                    }
+                    None => Err(warp::reject::custom(AuthError::UnknownUser)),
+                }
+            },
+        )
+}
+
+/// Like `with_auth`, but for routes (e.g. multipart uploads) whose body
+/// isn't the thing to sign: verifies `x-signature` over the `{id}` path
+/// segment instead of the request body, and extracts it alongside the
+/// verified owner id.
+pub fn with_id_auth(
+    user_store: UserStore,
+) -> impl Filter<Extract = (String, String), Error = warp::Rejection> + Clone {
+    warp::path::param::<String>()
+        .and(warp::header::<String>("x-user-id"))
+        .and(warp::header::<String>("x-signature"))
+        .and(with_user_store(user_store))
+        .and_then(
+            |id: String, user_id: String, signature: String, user_store: UserStore| async move {
+                match user_store.get_pubkey(&user_id) {
+                    Some(pubkey) => {
+                        if verify_signature(&pubkey, &signature, id.as_bytes()) {
+                            Ok((id, user_id))
+                        } else {
+                            Err(warp::reject::custom(AuthError::InvalidSignature))
+                        }
+                    }
+                    None => Err(warp::reject::custom(AuthError::UnknownUser)),
+                }
+            },
+        )
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterUserRequest {
+    pub pubkey: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegisterUserResponse {
+    pub id: String,
+}
+
+async fn register_user_handler(
+    req: RegisterUserRequest,
+    user_store: UserStore,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match user_store.register(req.pubkey) {
+        Some(id) => Ok(warp::reply::json(&RegisterUserResponse { id })),
+        None => Err(warp::reject::reject()),
+    }
+}
+
+fn route_register_user(
+    user_store: UserStore,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path("user")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_user_store(user_store))
+        .and_then(register_user_handler)
+}
+
+pub fn routes(
+    user_store: UserStore,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    route_register_user(user_store)
+}
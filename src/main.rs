@@ -1,15 +1,33 @@
+use model::ModelBackend;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+mod auth;
+mod metrics;
 mod model;
+mod sled_backend;
+
+fn new_backend() -> Result<Box<dyn ModelBackend>, String> {
+    match std::env::var("MODEL_BACKEND").as_deref() {
+        Ok("sled") => sled_backend::new_sled_model_store("sled_data")
+            .map(|store| Box::new(store) as Box<dyn ModelBackend>)
+            .map_err(|e| format!("{:?}", e)),
+        _ => model::new_sqlite_model_store()
+            .map(|store| Box::new(store) as Box<dyn ModelBackend>)
+            .map_err(|e| format!("{:?}", e)),
+    }
+}
 
 #[tokio::main]
 async fn main() {
-    match model::new_model_store() {
-        Ok(model_store) => {
+    match (new_backend(), auth::new_user_store()) {
+        (Ok(model_store), Ok(user_store)) => {
             let model_store = Arc::new(RwLock::new(model_store));
-            let routes = model::routes(model_store);
+            let metrics = metrics::new_metrics();
+            let routes = model::routes(model_store.clone(), user_store.clone(), metrics.clone())
+                .or(auth::routes(user_store))
+                .or(metrics::routes(model_store, metrics));
             warp::serve(routes).run(([127, 0, 0, 1], 3000)).await
         }
-        Err(_) => {}
+        _ => {}
     }
 }
@@ -0,0 +1,117 @@
+use crate::model::{with_model_store, ModelBackend};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use warp::Filter;
+
+#[derive(Default)]
+pub struct Metrics {
+    models_created: AtomicU64,
+    models_deleted: AtomicU64,
+    bytes_stored: AtomicU64,
+}
+
+pub type SharedMetrics = Arc<Metrics>;
+
+pub fn new_metrics() -> SharedMetrics {
+    Arc::new(Metrics::default())
+}
+
+impl Metrics {
+    pub fn record_create(&self, bytes: u64) {
+        self.models_created.fetch_add(1, Ordering::Relaxed);
+        self.bytes_stored.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn record_delete(&self) {
+        self.models_deleted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_bytes(&self, bytes: u64) {
+        self.bytes_stored.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn render(&self, model_count: i64) -> String {
+        format!(
+            "# HELP model_store_models_total Current number of models stored.\n\
+             # TYPE model_store_models_total gauge\n\
+             model_store_models_total {model_count}\n\
+             # HELP model_store_models_created_total Total number of models created.\n\
+             # TYPE model_store_models_created_total counter\n\
+             model_store_models_created_total {}\n\
+             # HELP model_store_models_deleted_total Total number of models deleted.\n\
+             # TYPE model_store_models_deleted_total counter\n\
+             model_store_models_deleted_total {}\n\
+             # HELP model_store_bytes_stored_total Cumulative bytes of model data stored.\n\
+             # TYPE model_store_bytes_stored_total counter\n\
+             model_store_bytes_stored_total {}\n",
+            self.models_created.load(Ordering::Relaxed),
+            self.models_deleted.load(Ordering::Relaxed),
+            self.bytes_stored.load(Ordering::Relaxed),
+        )
+    }
+}
+
+fn with_metrics(
+    metrics: SharedMetrics,
+) -> impl Filter<Extract = (SharedMetrics,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || metrics.clone())
+}
+
+#[derive(Debug)]
+pub struct DatabaseUnavailable;
+
+impl warp::reject::Reject for DatabaseUnavailable {}
+
+async fn health_handler(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let store = model_store.read().await;
+    if store.ping() {
+        Ok(warp::reply::json(&"ok"))
+    } else {
+        Err(warp::reject::custom(DatabaseUnavailable))
+    }
+}
+
+fn route_health(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path("health")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_model_store(model_store))
+        .and_then(health_handler)
+}
+
+async fn metrics_handler(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    metrics: SharedMetrics,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let store = model_store.read().await;
+    let model_count = store.count_models();
+    Ok(warp::reply::with_header(
+        metrics.render(model_count),
+        "content-type",
+        "text/plain; version=0.0.4",
+    ))
+}
+
+fn route_metrics(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    metrics: SharedMetrics,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path("metrics")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_model_store(model_store))
+        .and(with_metrics(metrics))
+        .and_then(metrics_handler)
+}
+
+pub fn routes(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    metrics: SharedMetrics,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    route_health(model_store.clone()).or(route_metrics(model_store, metrics))
+}
@@ -1,18 +1,173 @@
 use chrono::{DateTime, Local};
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
 use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 use warp::Filter;
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Model {
-    id: String,
-    name: String,
-    version: String,
-    data: String,
-    create_time: i64,
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) data: String,
+    pub(crate) digest: String,
+    pub(crate) owner_id: String,
+    pub(crate) create_time: i64,
+    pub(crate) is_file: bool,
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    NotFound,
+    Forbidden,
+}
+
+impl warp::reject::Reject for StoreError {}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ModelFilter {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub name: Option<String>,
+    pub created_after: Option<i64>,
+    pub created_before: Option<i64>,
+}
+
+/// A `Model` without its (potentially large inline, or filesystem-path)
+/// `data` field, for list endpoints — fetch the real bytes via
+/// `GET /model/{id}/data`.
+#[derive(Debug, Serialize)]
+pub struct ModelSummary {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub digest: String,
+    pub owner_id: String,
+    pub create_time: i64,
+    pub is_file: bool,
+}
+
+impl From<Model> for ModelSummary {
+    fn from(model: Model) -> Self {
+        ModelSummary {
+            id: model.id,
+            name: model.name,
+            version: model.version,
+            digest: model.digest,
+            owner_id: model.owner_id,
+            create_time: model.create_time,
+            is_file: model.is_file,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelPage {
+    pub models: Vec<ModelSummary>,
+    pub total: i64,
+}
+
+pub trait ModelBackend: Send + Sync {
+    fn add_model(
+        &mut self,
+        owner_id: String,
+        name: String,
+        version: String,
+        data: String,
+        metrics: &crate::metrics::SharedMetrics,
+    );
+    fn delete_model(
+        &mut self,
+        owner_id: String,
+        id: String,
+        metrics: &crate::metrics::SharedMetrics,
+    ) -> Result<(), StoreError>;
+    fn get_models(&self) -> Vec<Model>;
+    fn count_models(&self) -> i64;
+    fn update_model(
+        &mut self,
+        owner_id: String,
+        id: String,
+        name: String,
+        version: String,
+        data: String,
+    ) -> Result<(), StoreError>;
+    fn get_by_id(&self, id: String) -> Option<Model>;
+    fn set_data(
+        &mut self,
+        owner_id: String,
+        id: String,
+        data: String,
+        digest: String,
+        size: u64,
+        metrics: &crate::metrics::SharedMetrics,
+    ) -> Result<(), StoreError>;
+    fn ping(&self) -> bool;
+
+    fn check_ownership(&self, owner_id: &str, id: &str) -> Result<(), StoreError> {
+        match self.get_by_id(id.to_string()) {
+            Some(model) if model.owner_id == owner_id => Ok(()),
+            Some(_) => Err(StoreError::Forbidden),
+            None => Err(StoreError::NotFound),
+        }
+    }
+
+    fn get_versions(&self, name: String) -> Vec<Model> {
+        let mut models: Vec<Model> = self
+            .get_models()
+            .into_iter()
+            .filter(|model| model.name == name)
+            .collect();
+        models.sort_by_key(|model| model.create_time);
+        models
+    }
+
+    fn get_latest(&self, name: String) -> Option<Model> {
+        self.get_versions(name).into_iter().last()
+    }
+
+    fn list_models(&self, filter: ModelFilter) -> ModelPage {
+        let mut models: Vec<Model> = self.get_models();
+        if let Some(name) = &filter.name {
+            models.retain(|model| model.name.starts_with(name.as_str()));
+        }
+        if let Some(created_after) = filter.created_after {
+            models.retain(|model| model.create_time >= created_after);
+        }
+        if let Some(created_before) = filter.created_before {
+            models.retain(|model| model.create_time <= created_before);
+        }
+        models.sort_by_key(|model| model.create_time);
+        let total = models.len() as i64;
+        let offset = filter.offset.unwrap_or(0).max(0) as usize;
+        let models: Vec<ModelSummary> = match filter.limit {
+            Some(limit) if limit >= 0 => models
+                .into_iter()
+                .skip(offset)
+                .take(limit as usize)
+                .map(ModelSummary::from)
+                .collect(),
+            _ => models
+                .into_iter()
+                .skip(offset)
+                .map(ModelSummary::from)
+                .collect(),
+        };
+        ModelPage { models, total }
+    }
+}
+
+pub(crate) fn compute_digest(data: &[u8]) -> String {
+    let mut hasher = Sha3_256::new();
+    hasher.update(data);
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
 }
 
 #[derive(Clone)]
@@ -20,17 +175,62 @@ pub struct ModelStore {
     conn: Arc<Mutex<Connection>>,
 }
 
-const CREATE_MODEL_TABLE: &str = "CREATE TABLE IF NOT EXISTS models (id TEXT PRIMARY KEY, name TEXT, version TEXT, data BLOB, create_time INTEGER)";
+const CREATE_MODEL_TABLE: &str = "CREATE TABLE IF NOT EXISTS models (id TEXT PRIMARY KEY, name TEXT, version TEXT, data BLOB, digest TEXT, owner_id TEXT, create_time INTEGER, is_file INTEGER NOT NULL DEFAULT 0)";
+const CREATE_BLOBS_TABLE: &str = "CREATE TABLE IF NOT EXISTS blobs (digest TEXT PRIMARY KEY, data BLOB, refcount INTEGER NOT NULL DEFAULT 0)";
 const DELETE_BY_ID: &str = "DELETE FROM models WHERE id=:id";
-const INSERT_MODEL: &str = "INSERT INTO models (id, name, version, data, create_time) VALUES (:id, :name, :version, :data, :create_time)";
-const SELECT_ALL: &str = "SELECT * FROM models";
+const INSERT_MODEL: &str = "INSERT INTO models (id, name, version, data, digest, owner_id, create_time, is_file) VALUES (:id, :name, :version, :data, :digest, :owner_id, :create_time, :is_file)";
+const UPDATE_BY_ID: &str = "UPDATE models SET name=:name, version=:version, data=:data, digest=:digest, is_file=0 WHERE id=:id";
+const INCR_BLOB_REFCOUNT: &str = "UPDATE blobs SET refcount=refcount+1 WHERE digest=:digest";
+const INSERT_BLOB: &str = "INSERT INTO blobs (digest, data, refcount) VALUES (:digest, :data, 1)";
+const DECR_BLOB_REFCOUNT: &str = "UPDATE blobs SET refcount=refcount-1 WHERE digest=:digest";
+const DELETE_UNREFERENCED_BLOB: &str = "DELETE FROM blobs WHERE digest=:digest AND refcount<=0";
+const SUMMARY_COLUMNS: &str = "m.id, m.name, m.version, m.digest, m.owner_id, m.create_time, m.is_file";
+const SELECT_ALL: &str = "SELECT m.id, m.name, m.version, \
+    CASE WHEN m.is_file = 1 THEN m.data ELSE COALESCE(b.data, m.data) END, \
+    m.digest, m.owner_id, m.create_time, m.is_file \
+    FROM models m LEFT JOIN blobs b ON m.digest = b.digest";
+const SELECT_BY_NAME_ORDER_BY_CREATE_TIME: &str = "SELECT m.id, m.name, m.version, \
+    CASE WHEN m.is_file = 1 THEN m.data ELSE COALESCE(b.data, m.data) END, \
+    m.digest, m.owner_id, m.create_time, m.is_file \
+    FROM models m LEFT JOIN blobs b ON m.digest = b.digest \
+    WHERE m.name=:name ORDER BY m.create_time ASC";
+const SELECT_LATEST_BY_NAME: &str = "SELECT m.id, m.name, m.version, \
+    CASE WHEN m.is_file = 1 THEN m.data ELSE COALESCE(b.data, m.data) END, \
+    m.digest, m.owner_id, m.create_time, m.is_file \
+    FROM models m LEFT JOIN blobs b ON m.digest = b.digest \
+    WHERE m.name=:name ORDER BY m.create_time DESC LIMIT 1";
+const SELECT_BY_ID: &str = "SELECT m.id, m.name, m.version, \
+    CASE WHEN m.is_file = 1 THEN m.data ELSE COALESCE(b.data, m.data) END, \
+    m.digest, m.owner_id, m.create_time, m.is_file \
+    FROM models m LEFT JOIN blobs b ON m.digest = b.digest \
+    WHERE m.id=:id";
+const UPDATE_DATA_BY_ID: &str =
+    "UPDATE models SET data=:data, digest=:digest, is_file=1 WHERE id=:id";
+const SELECT_ONE: &str = "SELECT 1";
+const COUNT_MODELS: &str = "SELECT COUNT(*) FROM models";
+const CREATE_NAME_INDEX: &str = "CREATE INDEX IF NOT EXISTS idx_models_name ON models(name)";
+const CREATE_CREATE_TIME_INDEX: &str =
+    "CREATE INDEX IF NOT EXISTS idx_models_create_time ON models(create_time)";
 
-pub fn new_model_store() -> Result<ModelStore, rusqlite::Error> {
+const UPLOADS_DIR: &str = "uploads";
+
+pub fn new_sqlite_model_store() -> Result<ModelStore, rusqlite::Error> {
     match Connection::open("data.db") {
         Ok(conn) => match conn.execute(CREATE_MODEL_TABLE, ()) {
-            Ok(_) => Ok(ModelStore {
-                conn: Arc::new(Mutex::new(conn)),
-            }),
+            Ok(_) => {
+                if let Err(e) = conn.execute(CREATE_BLOBS_TABLE, ()) {
+                    println!("table error, err={:?}", e);
+                }
+                if let Err(e) = conn.execute(CREATE_NAME_INDEX, ()) {
+                    println!("index error, err={:?}", e);
+                }
+                if let Err(e) = conn.execute(CREATE_CREATE_TIME_INDEX, ()) {
+                    println!("index error, err={:?}", e);
+                }
+                Ok(ModelStore {
+                    conn: Arc::new(Mutex::new(conn)),
+                })
+            }
             Err(e) => Err(e),
         },
         Err(e) => Err(e),
@@ -38,34 +238,95 @@ pub fn new_model_store() -> Result<ModelStore, rusqlite::Error> {
 }
 
 impl ModelStore {
-    pub fn add_model(&mut self, name: String, version: String, data: String) {
+    /// Links `digest` to an existing blob (bumping its refcount) or stores
+    /// `data` as a brand-new blob, so identical content is only ever
+    /// persisted once.
+    fn retain_blob(&self, conn: &Connection, digest: &str, data: &str) {
+        match conn.execute(INCR_BLOB_REFCOUNT, &[(":digest", digest)]) {
+            Ok(updated) if updated > 0 => {}
+            _ => {
+                if let Err(e) = conn.execute(INSERT_BLOB, &[(":digest", digest), (":data", data)]) {
+                    println!("blob insert error, err={:?}", e);
+                }
+            }
+        }
+    }
+
+    /// Drops a model's reference to its blob, deleting the blob once no
+    /// model references it anymore.
+    fn release_blob(&self, conn: &Connection, digest: &str) {
+        if let Err(e) = conn.execute(DECR_BLOB_REFCOUNT, &[(":digest", digest)]) {
+            println!("blob decrement error, err={:?}", e);
+        }
+        if let Err(e) = conn.execute(DELETE_UNREFERENCED_BLOB, &[(":digest", digest)]) {
+            println!("blob prune error, err={:?}", e);
+        }
+    }
+}
+
+impl ModelBackend for ModelStore {
+    fn add_model(
+        &mut self,
+        owner_id: String,
+        name: String,
+        version: String,
+        data: String,
+        metrics: &crate::metrics::SharedMetrics,
+    ) {
         let id: Uuid = Uuid::new_v4();
         let now: DateTime<Local> = Local::now();
+        let digest = compute_digest(data.as_bytes());
+        let bytes_stored = data.len() as u64;
         let conn = self.conn.lock().unwrap();
+        self.retain_blob(&conn, &digest, &data);
         match conn.execute(
             INSERT_MODEL,
             &[
                 (":id", id.to_string().as_str()),
                 (":name", name.as_str()),
                 (":version", version.as_str()),
-                (":data", data.as_str()),
+                (":data", digest.as_str()),
+                (":digest", digest.as_str()),
+                (":owner_id", owner_id.as_str()),
                 (":create_time", &(now.timestamp_millis().to_string())),
+                (":is_file", "0"),
             ],
         ) {
-            Ok(updated) => println!("{} rows were updated", updated),
+            Ok(updated) => {
+                println!("{} rows were updated", updated);
+                metrics.record_create(bytes_stored);
+            }
             Err(e) => println!("insert error, err={:?}", e),
         }
     }
 
-    pub fn delete_model(&mut self, id: String) {
+    fn delete_model(
+        &mut self,
+        owner_id: String,
+        id: String,
+        metrics: &crate::metrics::SharedMetrics,
+    ) -> Result<(), StoreError> {
+        self.check_ownership(&owner_id, &id)?;
+        let model = self.get_by_id(id.clone());
         let conn = self.conn.lock().unwrap();
         match conn.execute(DELETE_BY_ID, &[(":id", &id)]) {
-            Ok(deleted) => println!("{} rows were deleted", deleted),
+            Ok(deleted) => {
+                println!("{} rows were deleted", deleted);
+                if deleted > 0 {
+                    metrics.record_delete();
+                }
+            }
             Err(e) => println!("delete error, err={:?}", e),
         }
+        if let Some(model) = model {
+            if !model.is_file {
+                self.release_blob(&conn, &model.digest);
+            }
+        }
+        Ok(())
     }
 
-    pub fn get_models(&self) -> Vec<Model> {
+    fn get_models(&self) -> Vec<Model> {
         let conn = self.conn.lock().unwrap();
         let mut models: Vec<Model> = vec![];
         match conn.prepare(SELECT_ALL) {
@@ -75,7 +336,10 @@ impl ModelStore {
                     name: row.get(1)?,
                     version: row.get(2)?,
                     data: row.get(3)?,
-                    create_time: row.get(4)?,
+                    digest: row.get(4)?,
+                    owner_id: row.get(5)?,
+                    create_time: row.get(6)?,
+                    is_file: row.get(7)?,
                 })
             }) {
                 Ok(model_iter) => {
@@ -90,14 +354,237 @@ impl ModelStore {
         }
         models
     }
+
+    fn count_models(&self) -> i64 {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(COUNT_MODELS, [], |row| row.get(0))
+            .unwrap_or(0)
+    }
+
+    fn update_model(
+        &mut self,
+        owner_id: String,
+        id: String,
+        name: String,
+        version: String,
+        data: String,
+    ) -> Result<(), StoreError> {
+        self.check_ownership(&owner_id, &id)?;
+        let existing = self.get_by_id(id.clone()).ok_or(StoreError::NotFound)?;
+        let digest = compute_digest(data.as_bytes());
+        let conn = self.conn.lock().unwrap();
+        self.retain_blob(&conn, &digest, &data);
+        match conn.execute(
+            UPDATE_BY_ID,
+            &[
+                (":id", id.as_str()),
+                (":name", name.as_str()),
+                (":version", version.as_str()),
+                (":data", digest.as_str()),
+                (":digest", digest.as_str()),
+            ],
+        ) {
+            Ok(updated) => println!("{} rows were updated", updated),
+            Err(e) => println!("update error, err={:?}", e),
+        }
+        if !existing.is_file {
+            self.release_blob(&conn, &existing.digest);
+        }
+        Ok(())
+    }
+
+    fn get_versions(&self, name: String) -> Vec<Model> {
+        let conn = self.conn.lock().unwrap();
+        let mut models: Vec<Model> = vec![];
+        match conn.prepare(SELECT_BY_NAME_ORDER_BY_CREATE_TIME) {
+            Ok(mut stmt) => match stmt.query_map(&[(":name", name.as_str())], |row| {
+                Ok(Model {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    version: row.get(2)?,
+                    data: row.get(3)?,
+                    digest: row.get(4)?,
+                    owner_id: row.get(5)?,
+                    create_time: row.get(6)?,
+                    is_file: row.get(7)?,
+                })
+            }) {
+                Ok(model_iter) => {
+                    for model in model_iter {
+                        models.push(model.unwrap());
+                    }
+                }
+
+                Err(_) => {}
+            },
+            Err(_) => {}
+        }
+        models
+    }
+
+    fn get_latest(&self, name: String) -> Option<Model> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(SELECT_LATEST_BY_NAME, &[(":name", name.as_str())], |row| {
+            Ok(Model {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                version: row.get(2)?,
+                data: row.get(3)?,
+                digest: row.get(4)?,
+                owner_id: row.get(5)?,
+                create_time: row.get(6)?,
+                is_file: row.get(7)?,
+            })
+        }) {
+            Ok(model) => Some(model),
+            Err(_) => None,
+        }
+    }
+
+    fn get_by_id(&self, id: String) -> Option<Model> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(SELECT_BY_ID, &[(":id", id.as_str())], |row| {
+            Ok(Model {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                version: row.get(2)?,
+                data: row.get(3)?,
+                digest: row.get(4)?,
+                owner_id: row.get(5)?,
+                create_time: row.get(6)?,
+                is_file: row.get(7)?,
+            })
+        }) {
+            Ok(model) => Some(model),
+            Err(_) => None,
+        }
+    }
+
+    fn set_data(
+        &mut self,
+        owner_id: String,
+        id: String,
+        data: String,
+        digest: String,
+        size: u64,
+        metrics: &crate::metrics::SharedMetrics,
+    ) -> Result<(), StoreError> {
+        self.check_ownership(&owner_id, &id)?;
+        let existing = self.get_by_id(id.clone()).ok_or(StoreError::NotFound)?;
+        let conn = self.conn.lock().unwrap();
+        match conn.execute(
+            UPDATE_DATA_BY_ID,
+            &[
+                (":id", id.as_str()),
+                (":data", data.as_str()),
+                (":digest", digest.as_str()),
+            ],
+        ) {
+            Ok(updated) => {
+                println!("{} rows were updated", updated);
+                if updated > 0 {
+                    metrics.record_bytes(size);
+                    if !existing.is_file {
+                        self.release_blob(&conn, &existing.digest);
+                    }
+                }
+            }
+            Err(e) => println!("update error, err={:?}", e),
+        }
+        Ok(())
+    }
+
+    fn ping(&self) -> bool {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(SELECT_ONE, [], |_| Ok(())).is_ok()
+    }
+
+    fn list_models(&self, filter: ModelFilter) -> ModelPage {
+        let conn = self.conn.lock().unwrap();
+        let mut clauses: Vec<String> = vec![];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
+
+        if let Some(name) = &filter.name {
+            clauses.push("m.name LIKE ?".to_string());
+            params.push(Box::new(format!("{}%", name)));
+        }
+        if let Some(created_after) = filter.created_after {
+            clauses.push("m.create_time >= ?".to_string());
+            params.push(Box::new(created_after));
+        }
+        if let Some(created_before) = filter.created_before {
+            clauses.push("m.create_time <= ?".to_string());
+            params.push(Box::new(created_before));
+        }
+        let where_clause = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", clauses.join(" AND "))
+        };
+
+        let count_sql = format!("SELECT COUNT(*) FROM models m{}", where_clause);
+        let total: i64 = conn
+            .query_row(&count_sql, rusqlite::params_from_iter(params.iter()), |row| {
+                row.get(0)
+            })
+            .unwrap_or(0);
+
+        let mut query_sql = format!(
+            "SELECT {} FROM models m{} ORDER BY m.create_time ASC",
+            SUMMARY_COLUMNS, where_clause
+        );
+        match filter.limit {
+            Some(limit) => {
+                query_sql.push_str(" LIMIT ?");
+                params.push(Box::new(limit));
+            }
+            None if filter.offset.is_some() => query_sql.push_str(" LIMIT -1"),
+            None => {}
+        }
+        if let Some(offset) = filter.offset {
+            query_sql.push_str(" OFFSET ?");
+            params.push(Box::new(offset));
+        }
+
+        let mut models: Vec<ModelSummary> = vec![];
+        match conn.prepare(&query_sql) {
+            Ok(mut stmt) => match stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                Ok(ModelSummary {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    version: row.get(2)?,
+                    digest: row.get(3)?,
+                    owner_id: row.get(4)?,
+                    create_time: row.get(5)?,
+                    is_file: row.get(6)?,
+                })
+            }) {
+                Ok(model_iter) => {
+                    for model in model_iter {
+                        models.push(model.unwrap());
+                    }
+                }
+                Err(_) => {}
+            },
+            Err(_) => {}
+        }
+
+        ModelPage { models, total }
+    }
 }
 
-fn with_model_store(
-    model_store: Arc<RwLock<ModelStore>>,
-) -> impl Filter<Extract = (Arc<RwLock<ModelStore>>,), Error = std::convert::Infallible> + Clone {
+pub(crate) fn with_model_store(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> impl Filter<Extract = (Arc<RwLock<Box<dyn ModelBackend>>>,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || model_store.clone())
 }
 
+fn with_metrics(
+    metrics: crate::metrics::SharedMetrics,
+) -> impl Filter<Extract = (crate::metrics::SharedMetrics,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || metrics.clone())
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateModelRequest {
     pub name: String,
@@ -106,36 +593,49 @@ pub struct CreateModelRequest {
 }
 
 async fn create_model_handler(
-    req: CreateModelRequest,
-    model_store: Arc<RwLock<ModelStore>>,
+    owner_id: String,
+    body: bytes::Bytes,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    metrics: crate::metrics::SharedMetrics,
 ) -> Result<impl warp::Reply, warp::Rejection> {
+    let req: CreateModelRequest = match serde_json::from_slice(&body) {
+        Ok(req) => req,
+        Err(_) => return Err(warp::reject::reject()),
+    };
     let mut store = model_store.write().await;
-    store.add_model(req.name, req.version, req.data);
+    store.add_model(owner_id, req.name, req.version, req.data, &metrics);
     Ok(warp::reply::json(&"create success"))
 }
 
 fn route_create_model(
-    model_store: Arc<RwLock<ModelStore>>,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    user_store: crate::auth::UserStore,
+    metrics: crate::metrics::SharedMetrics,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path("model")
+        .and(warp::path::end())
         .and(warp::post())
-        .and(warp::body::json())
+        .and(crate::auth::with_auth(user_store))
         .and(with_model_store(model_store))
+        .and(with_metrics(metrics))
         .and_then(create_model_handler)
 }
 
 async fn get_model_handler(
-    model_store: Arc<RwLock<ModelStore>>,
+    filter: ModelFilter,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let store = model_store.read().await;
-    Ok(warp::reply::json(&store.get_models()))
+    Ok(warp::reply::json(&store.list_models(filter)))
 }
 
 fn route_get_models(
-    model_store: Arc<RwLock<ModelStore>>,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path("model")
+        .and(warp::path::end())
         .and(warp::get())
+        .and(warp::query::<ModelFilter>())
         .and(with_model_store(model_store))
         .and_then(get_model_handler)
 }
@@ -146,28 +646,324 @@ pub struct DeleteModelRequest {
 }
 
 async fn delete_model_handler(
-    req: DeleteModelRequest,
-    model_store: Arc<RwLock<ModelStore>>,
+    owner_id: String,
+    body: bytes::Bytes,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    metrics: crate::metrics::SharedMetrics,
 ) -> Result<impl warp::Reply, warp::Rejection> {
+    let req: DeleteModelRequest = match serde_json::from_slice(&body) {
+        Ok(req) => req,
+        Err(_) => return Err(warp::reject::reject()),
+    };
     let mut store = model_store.write().await;
-    store.delete_model(req.id);
+    store
+        .delete_model(owner_id, req.id, &metrics)
+        .map_err(warp::reject::custom)?;
     Ok(warp::reply::json(&"delete success"))
 }
 
 fn route_delete_model(
-    model_store: Arc<RwLock<ModelStore>>,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    user_store: crate::auth::UserStore,
+    metrics: crate::metrics::SharedMetrics,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path("model")
+        .and(warp::path::end())
         .and(warp::delete())
-        .and(warp::body::json())
+        .and(crate::auth::with_auth(user_store))
         .and(with_model_store(model_store))
+        .and(with_metrics(metrics))
         .and_then(delete_model_handler)
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateModelRequest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub data: String,
+}
+
+async fn update_model_handler(
+    owner_id: String,
+    body: bytes::Bytes,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let req: UpdateModelRequest = match serde_json::from_slice(&body) {
+        Ok(req) => req,
+        Err(_) => return Err(warp::reject::reject()),
+    };
+    let mut store = model_store.write().await;
+    store
+        .update_model(owner_id, req.id, req.name, req.version, req.data)
+        .map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&"update success"))
+}
+
+fn route_update_model(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    user_store: crate::auth::UserStore,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path("model")
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(crate::auth::with_auth(user_store))
+        .and(with_model_store(model_store))
+        .and_then(update_model_handler)
+}
+
+async fn get_versions_handler(
+    name: String,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let store = model_store.read().await;
+    Ok(warp::reply::json(&store.get_versions(name)))
+}
+
+fn route_get_versions(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("model" / String / "versions")
+        .and(warp::get())
+        .and(with_model_store(model_store))
+        .and_then(get_versions_handler)
+}
+
+async fn get_latest_handler(
+    name: String,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let store = model_store.read().await;
+    Ok(warp::reply::json(&store.get_latest(name)))
+}
+
+fn route_get_latest(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("model" / String / "latest")
+        .and(warp::get())
+        .and(with_model_store(model_store))
+        .and_then(get_latest_handler)
+}
+
+async fn upload_data_handler(
+    id: String,
+    owner_id: String,
+    form: warp::multipart::FormData,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    metrics: crate::metrics::SharedMetrics,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    use bytes::Buf;
+    use futures::TryStreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    {
+        let store = model_store.read().await;
+        store
+            .check_ownership(&owner_id, &id)
+            .map_err(warp::reject::custom)?;
+    }
+
+    match form.try_collect::<Vec<warp::multipart::Part>>().await {
+        Ok(mut parts) => match parts.pop() {
+            Some(part) => match std::fs::create_dir_all(UPLOADS_DIR) {
+                Ok(_) => {
+                    let file_id: Uuid = Uuid::new_v4();
+                    let path = format!("{}/{}", UPLOADS_DIR, file_id);
+                    let mut file = match tokio::fs::File::create(&path).await {
+                        Ok(file) => file,
+                        Err(e) => {
+                            println!("upload create error, err={:?}", e);
+                            return Err(warp::reject::reject());
+                        }
+                    };
+                    let mut hasher = Sha3_256::new();
+                    let mut size: u64 = 0;
+                    let mut stream = part.stream();
+                    let write_result: std::io::Result<()> = async {
+                        while let Some(buf) = stream
+                            .try_next()
+                            .await
+                            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?
+                        {
+                            let chunk = buf.chunk();
+                            hasher.update(chunk);
+                            size += chunk.len() as u64;
+                            file.write_all(chunk).await?;
+                        }
+                        file.flush().await
+                    }
+                    .await;
+                    match write_result {
+                        Ok(_) => {
+                            let digest = hasher
+                                .finalize()
+                                .iter()
+                                .map(|b| format!("{:02x}", b))
+                                .collect::<String>();
+                            let mut store = model_store.write().await;
+                            store
+                                .set_data(owner_id, id, path, digest, size, &metrics)
+                                .map_err(warp::reject::custom)?;
+                            Ok(warp::reply::json(&"upload success"))
+                        }
+                        Err(e) => {
+                            println!("upload write error, err={:?}", e);
+                            Err(warp::reject::reject())
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("uploads dir error, err={:?}", e);
+                    Err(warp::reject::reject())
+                }
+            },
+            None => Err(warp::reject::reject()),
+        },
+        Err(e) => {
+            println!("multipart error, err={:?}", e);
+            Err(warp::reject::reject())
+        }
+    }
+}
+
+fn route_upload_data(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    user_store: crate::auth::UserStore,
+    metrics: crate::metrics::SharedMetrics,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path("model")
+        .and(crate::auth::with_id_auth(user_store))
+        .and(warp::path("data"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::multipart::form())
+        .and(with_model_store(model_store))
+        .and(with_metrics(metrics))
+        .and_then(upload_data_handler)
+}
+
+async fn get_data_handler(
+    id: String,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let store = model_store.read().await;
+    match store.get_by_id(id) {
+        Some(model) => {
+            if model.is_file {
+                let file = match tokio::fs::File::open(&model.data).await {
+                    Ok(file) => file,
+                    Err(e) => {
+                        println!("read error, err={:?}", e);
+                        return Err(warp::reject::reject());
+                    }
+                };
+                let len = match file.metadata().await {
+                    Ok(meta) => meta.len(),
+                    Err(e) => {
+                        println!("read error, err={:?}", e);
+                        return Err(warp::reject::reject());
+                    }
+                };
+                let stream = tokio_util::io::ReaderStream::new(file);
+                match warp::http::Response::builder()
+                    .header("Content-Length", len)
+                    .body(warp::hyper::Body::wrap_stream(stream))
+                {
+                    Ok(response) => Ok(response),
+                    Err(_) => Err(warp::reject::reject()),
+                }
+            } else {
+                let bytes = model.data.into_bytes();
+                match warp::http::Response::builder()
+                    .header("Content-Length", bytes.len())
+                    .body(warp::hyper::Body::from(bytes))
+                {
+                    Ok(response) => Ok(response),
+                    Err(_) => Err(warp::reject::reject()),
+                }
+            }
+        }
+        None => Err(warp::reject::not_found()),
+    }
+}
+
+fn route_get_data(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("model" / String / "data")
+        .and(warp::get())
+        .and(with_model_store(model_store))
+        .and_then(get_data_handler)
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyResponse {
+    pub matches: bool,
+    pub digest: String,
+}
+
+async fn verify_model_handler(
+    id: String,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let store = model_store.read().await;
+    match store.get_by_id(id) {
+        Some(model) => {
+            let bytes = if model.is_file {
+                match tokio::fs::read(&model.data).await {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        println!("read error, err={:?}", e);
+                        return Err(warp::reject::reject());
+                    }
+                }
+            } else {
+                model.data.clone().into_bytes()
+            };
+            let digest = compute_digest(&bytes);
+            Ok(warp::reply::json(&VerifyResponse {
+                matches: digest == model.digest,
+                digest,
+            }))
+        }
+        None => Err(warp::reject::not_found()),
+    }
+}
+
+fn route_verify_model(
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("model" / String / "verify")
+        .and(warp::get())
+        .and(with_model_store(model_store))
+        .and_then(verify_model_handler)
+}
+
 pub fn routes(
-    model_store: Arc<RwLock<ModelStore>>,
+    model_store: Arc<RwLock<Box<dyn ModelBackend>>>,
+    user_store: crate::auth::UserStore,
+    metrics: crate::metrics::SharedMetrics,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     route_get_models(model_store.clone())
-        .or(route_create_model(model_store.clone()))
-        .or(route_delete_model(model_store.clone()))
+        .or(route_create_model(
+            model_store.clone(),
+            user_store.clone(),
+            metrics.clone(),
+        ))
+        .or(route_delete_model(
+            model_store.clone(),
+            user_store.clone(),
+            metrics.clone(),
+        ))
+        .or(route_update_model(model_store.clone(), user_store.clone()))
+        .or(route_get_versions(model_store.clone()))
+        .or(route_get_latest(model_store.clone()))
+        .or(route_upload_data(
+            model_store.clone(),
+            user_store.clone(),
+            metrics.clone(),
+        ))
+        .or(route_get_data(model_store.clone()))
+        .or(route_verify_model(model_store.clone()))
 }
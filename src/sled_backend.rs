@@ -0,0 +1,248 @@
+use crate::model::{compute_digest, Model, ModelBackend, StoreError};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct SledModelStore {
+    tree: sled::Db,
+    blobs: sled::Tree,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Blob {
+    data: String,
+    refcount: u64,
+}
+
+pub fn new_sled_model_store(path: &str) -> Result<SledModelStore, sled::Error> {
+    let tree = sled::open(path)?;
+    let blobs = tree.open_tree("blobs")?;
+    Ok(SledModelStore { tree, blobs })
+}
+
+impl SledModelStore {
+    /// Links `digest` to an existing blob (bumping its refcount) or stores
+    /// `data` as a brand-new blob, so identical content is only ever
+    /// persisted once.
+    fn retain_blob(&self, digest: &str, data: &str) {
+        let blob = match self.blobs.get(digest.as_bytes()) {
+            Ok(Some(bytes)) => match serde_json::from_slice::<Blob>(&bytes) {
+                Ok(mut blob) => {
+                    blob.refcount += 1;
+                    blob
+                }
+                Err(_) => Blob {
+                    data: data.to_string(),
+                    refcount: 1,
+                },
+            },
+            _ => Blob {
+                data: data.to_string(),
+                refcount: 1,
+            },
+        };
+        match serde_json::to_vec(&blob) {
+            Ok(bytes) => {
+                if let Err(e) = self.blobs.insert(digest.as_bytes(), bytes) {
+                    println!("blob insert error, err={:?}", e);
+                }
+            }
+            Err(e) => println!("blob insert error, err={:?}", e),
+        }
+    }
+
+    /// Drops a model's reference to its blob, deleting the blob once no
+    /// model references it anymore.
+    fn release_blob(&self, digest: &str) {
+        match self.blobs.get(digest.as_bytes()) {
+            Ok(Some(bytes)) => match serde_json::from_slice::<Blob>(&bytes) {
+                Ok(mut blob) if blob.refcount > 1 => {
+                    blob.refcount -= 1;
+                    match serde_json::to_vec(&blob) {
+                        Ok(bytes) => {
+                            if let Err(e) = self.blobs.insert(digest.as_bytes(), bytes) {
+                                println!("blob update error, err={:?}", e);
+                            }
+                        }
+                        Err(e) => println!("blob update error, err={:?}", e),
+                    }
+                }
+                _ => {
+                    if let Err(e) = self.blobs.remove(digest.as_bytes()) {
+                        println!("blob prune error, err={:?}", e);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Fills in a non-file-backed model's real content from the blob table,
+    /// since `model.data` only holds the content digest on disk.
+    fn resolve_data(&self, mut model: Model) -> Model {
+        if !model.is_file {
+            if let Ok(Some(bytes)) = self.blobs.get(model.digest.as_bytes()) {
+                if let Ok(blob) = serde_json::from_slice::<Blob>(&bytes) {
+                    model.data = blob.data;
+                }
+            }
+        }
+        model
+    }
+}
+
+impl ModelBackend for SledModelStore {
+    fn add_model(
+        &mut self,
+        owner_id: String,
+        name: String,
+        version: String,
+        data: String,
+        metrics: &crate::metrics::SharedMetrics,
+    ) {
+        let id: Uuid = Uuid::new_v4();
+        let now: DateTime<Local> = Local::now();
+        let digest = compute_digest(data.as_bytes());
+        let bytes_stored = data.len() as u64;
+        self.retain_blob(&digest, &data);
+        let model = Model {
+            id: id.to_string(),
+            name,
+            version,
+            data: digest.clone(),
+            digest,
+            owner_id,
+            create_time: now.timestamp_millis(),
+            is_file: false,
+        };
+        match serde_json::to_vec(&model) {
+            Ok(bytes) => match self.tree.insert(model.id.as_bytes(), bytes) {
+                Ok(_) => {
+                    println!("1 rows were updated");
+                    metrics.record_create(bytes_stored);
+                }
+                Err(e) => println!("insert error, err={:?}", e),
+            },
+            Err(e) => println!("insert error, err={:?}", e),
+        }
+    }
+
+    fn delete_model(
+        &mut self,
+        owner_id: String,
+        id: String,
+        metrics: &crate::metrics::SharedMetrics,
+    ) -> Result<(), StoreError> {
+        self.check_ownership(&owner_id, &id)?;
+        let model = self.get_by_id(id.clone());
+        match self.tree.remove(id.as_bytes()) {
+            Ok(removed) => {
+                println!("{} rows were deleted", removed.is_some() as i32);
+                if removed.is_some() {
+                    metrics.record_delete();
+                }
+            }
+            Err(e) => println!("delete error, err={:?}", e),
+        }
+        if let Some(model) = model {
+            if !model.is_file {
+                self.release_blob(&model.digest);
+            }
+        }
+        Ok(())
+    }
+
+    fn get_models(&self) -> Vec<Model> {
+        self.tree
+            .iter()
+            .values()
+            .filter_map(|v| v.ok())
+            .filter_map(|v| serde_json::from_slice::<Model>(&v).ok())
+            .map(|model| self.resolve_data(model))
+            .collect()
+    }
+
+    fn count_models(&self) -> i64 {
+        self.tree.len() as i64
+    }
+
+    fn update_model(
+        &mut self,
+        owner_id: String,
+        id: String,
+        name: String,
+        version: String,
+        data: String,
+    ) -> Result<(), StoreError> {
+        self.check_ownership(&owner_id, &id)?;
+        let existing = self.get_by_id(id.clone()).ok_or(StoreError::NotFound)?;
+        let digest = compute_digest(data.as_bytes());
+        self.retain_blob(&digest, &data);
+        let model = Model {
+            id,
+            name,
+            version,
+            data: digest.clone(),
+            digest,
+            owner_id,
+            create_time: existing.create_time,
+            is_file: false,
+        };
+        match serde_json::to_vec(&model) {
+            Ok(bytes) => match self.tree.insert(model.id.as_bytes(), bytes) {
+                Ok(_) => println!("1 rows were updated"),
+                Err(e) => println!("update error, err={:?}", e),
+            },
+            Err(e) => println!("update error, err={:?}", e),
+        }
+        if !existing.is_file {
+            self.release_blob(&existing.digest);
+        }
+        Ok(())
+    }
+
+    fn get_by_id(&self, id: String) -> Option<Model> {
+        match self.tree.get(id.as_bytes()) {
+            Ok(Some(bytes)) => serde_json::from_slice::<Model>(&bytes)
+                .ok()
+                .map(|model| self.resolve_data(model)),
+            _ => None,
+        }
+    }
+
+    fn ping(&self) -> bool {
+        self.tree.generate_id().is_ok()
+    }
+
+    fn set_data(
+        &mut self,
+        owner_id: String,
+        id: String,
+        data: String,
+        digest: String,
+        size: u64,
+        metrics: &crate::metrics::SharedMetrics,
+    ) -> Result<(), StoreError> {
+        self.check_ownership(&owner_id, &id)?;
+        let existing = self.get_by_id(id.clone()).ok_or(StoreError::NotFound)?;
+        let mut model = existing.clone();
+        model.data = data;
+        model.digest = digest;
+        model.is_file = true;
+        match serde_json::to_vec(&model) {
+            Ok(bytes) => match self.tree.insert(id.as_bytes(), bytes) {
+                Ok(_) => {
+                    println!("1 rows were updated");
+                    metrics.record_bytes(size);
+                    if !existing.is_file {
+                        self.release_blob(&existing.digest);
+                    }
+                }
+                Err(e) => println!("update error, err={:?}", e),
+            },
+            Err(e) => println!("update error, err={:?}", e),
+        }
+        Ok(())
+    }
+}